@@ -1,6 +1,6 @@
-use ocl::{Queue, Buffer, Kernel, Context, Program, builders::DeviceSpecifier, error::Result};
+use ocl::{Queue, Buffer, Kernel, Context, Program, Event, Device, OclPrm, flags, builders::DeviceSpecifier, error::Result};
 
-#[derive(PartialEq, Eq)]
+#[derive(PartialEq, Eq, Clone, Copy)]
 /// An operation that can be used to map over data
 pub enum Op {
 	Add,
@@ -8,32 +8,109 @@ pub enum Op {
 	Mul,
 	Div,
 	Mod,
+	Max,
 	None
 }
 
+/// A type that can be used as the element type of a `MapProgram`/`MapKernel`, mapping to an OpenCL C scalar type
+pub trait Scalar: OclPrm {
+	/// The OpenCL C type name substituted into generated kernel source
+	const NAME: &'static str;
+	/// Any `#pragma` required to use this type in an OpenCL C kernel, empty if none
+	const PRAGMA: &'static str = "";
+	/// Whether this type is an OpenCL C integer type, where `%`/`/` are integer operators rather than `fmod`/float division
+	const IS_INTEGRAL: bool = false;
+
+	/// Formats this value as an OpenCL C literal of this type
+	fn literal(self) -> String;
+}
+
+impl Scalar for f32 {
+	const NAME: &'static str = "float";
+
+	fn literal(self) -> String { format!("{:?}f", self) }
+}
+
+impl Scalar for f64 {
+	const NAME: &'static str = "double";
+	const PRAGMA: &'static str = "#pragma OPENCL EXTENSION cl_khr_fp64 : enable\n";
+
+	fn literal(self) -> String { format!("{:?}", self) }
+}
+
+impl Scalar for i32 {
+	const NAME: &'static str = "int";
+	const IS_INTEGRAL: bool = true;
+
+	fn literal(self) -> String { format!("{}", self) }
+}
+
+impl Scalar for u32 {
+	const NAME: &'static str = "uint";
+	const IS_INTEGRAL: bool = true;
+
+	fn literal(self) -> String { format!("{}", self) }
+}
+
 /// A safe wrapper type for Program
 pub struct MapProgram(Program);
 /// A safe wrapper type for Kernel
 pub struct MapKernel(Kernel, usize);
 
 impl MapProgram {
-	/// Creates a new program the uses given device to apply given mapping operation over data with given context
-	pub fn from<D: Into<DeviceSpecifier>>(devices: D, op: Op, context: &Context) -> Result<Self> {
+	/// Creates a new program the uses given device to apply given mapping operation over data of type `T` with given context
+	pub fn from<T: Scalar, D: Into<DeviceSpecifier>>(devices: D, op: Op, context: &Context) -> Result<Self> {
+		let src = if op == Op::None {
+			format!("{}__kernel void __main__(__global {ty}* buffer, {ty} scalar) {{}}", T::PRAGMA, ty = T::NAME)
+		} else {
+			let stmt = match op {
+				Op::Max => return Err("Max is only supported by ReduceProgram".into()),
+				Op::None => panic!("creating program failed"),
+				// `%` is not defined on floating-point operands in OpenCL C; `fmod` is the float/double equivalent
+				Op::Mod if !T::IS_INTEGRAL => String::from("buffer[get_global_id(0)] = fmod(buffer[get_global_id(0)], scalar);"),
+				Op::Add => String::from("buffer[get_global_id(0)] += scalar;"),
+				Op::Min => String::from("buffer[get_global_id(0)] -= scalar;"),
+				Op::Mul => String::from("buffer[get_global_id(0)] *= scalar;"),
+				Op::Div => String::from("buffer[get_global_id(0)] /= scalar;"),
+				Op::Mod => String::from("buffer[get_global_id(0)] %= scalar;")
+			};
+
+			format!(r#"{pragma}
+				__kernel void __main__(__global {ty}* buffer, {ty} scalar) {{
+					{stmt}
+				}}
+			"#, pragma = T::PRAGMA, ty = T::NAME, stmt = stmt)
+		};
+
+		Program::builder()
+			.devices(devices)
+			.src(src)
+			.build(&context)
+			.map(|program| Self(program))
+	}
+
+	/// Creates a new program that uses given device to apply given operation element-wise between two buffers of type `T` with given context
+	pub fn from_binary<T: Scalar, D: Into<DeviceSpecifier>>(devices: D, op: Op, context: &Context) -> Result<Self> {
 		let src = if op == Op::None {
-			String::from("__kernel void __main__(__global float* buffer, float scalar) {}")
+			format!("{}__kernel void __main__(__global {ty} const* src, __global {ty} const* rhs, __global {ty}* res) {{}}", T::PRAGMA, ty = T::NAME)
 		} else {
-			format!(r#"
-				__kernel void __main__(__global float* buffer, float scalar) {{
-					buffer[get_global_id(0)] {}= scalar;
+			let stmt = match op {
+				Op::Max => return Err("Max is only supported by ReduceProgram".into()),
+				Op::None => panic!("creating program failed"),
+				// `%` is not defined on floating-point operands in OpenCL C; `fmod` is the float/double equivalent
+				Op::Mod if !T::IS_INTEGRAL => String::from("res[get_global_id(0)] = fmod(src[get_global_id(0)], rhs[get_global_id(0)]);"),
+				Op::Add => String::from("res[get_global_id(0)] = src[get_global_id(0)] + rhs[get_global_id(0)];"),
+				Op::Min => String::from("res[get_global_id(0)] = src[get_global_id(0)] - rhs[get_global_id(0)];"),
+				Op::Mul => String::from("res[get_global_id(0)] = src[get_global_id(0)] * rhs[get_global_id(0)];"),
+				Op::Div => String::from("res[get_global_id(0)] = src[get_global_id(0)] / rhs[get_global_id(0)];"),
+				Op::Mod => String::from("res[get_global_id(0)] = src[get_global_id(0)] % rhs[get_global_id(0)];")
+			};
+
+			format!(r#"{pragma}
+				__kernel void __main__(__global {ty} const* src, __global {ty} const* rhs, __global {ty}* res) {{
+					{stmt}
 				}}
-			"#, match op {
-				Op::Add => "+",
-				Op::Min => "-",
-				Op::Mul => "*",
-				Op::Div => "/",
-				Op::Mod => "%",
-				Op::None => panic!("creating program failed")
-			})
+			"#, pragma = T::PRAGMA, ty = T::NAME, stmt = stmt)
 		};
 
 		Program::builder()
@@ -42,11 +119,56 @@ impl MapProgram {
 			.build(&context)
 			.map(|program| Self(program))
 	}
+
+	/// Creates a new program that uses given device to apply given chain of operations over data of type `T` in a single pass with given context
+	pub fn from_chain<T: Scalar, D: Into<DeviceSpecifier>>(devices: D, ops: &[(Op, T)], context: &Context) -> Result<Self> {
+		let mut body = format!("{ty} v = buffer[get_global_id(0)];\n", ty = T::NAME);
+
+		for &(op, constant) in ops {
+			match op {
+				Op::Max => return Err("Max is only supported by ReduceProgram".into()),
+				Op::None => continue,
+				// `%` is not defined on floating-point operands in OpenCL C; `fmod` is the float/double equivalent
+				Op::Mod if !T::IS_INTEGRAL => body.push_str(&format!("\t\t\t\tv = fmod(v, {});\n", constant.literal())),
+				Op::Add => body.push_str(&format!("\t\t\t\tv = v + {};\n", constant.literal())),
+				Op::Min => body.push_str(&format!("\t\t\t\tv = v - {};\n", constant.literal())),
+				Op::Mul => body.push_str(&format!("\t\t\t\tv = v * {};\n", constant.literal())),
+				Op::Div => body.push_str(&format!("\t\t\t\tv = v / {};\n", constant.literal())),
+				Op::Mod => body.push_str(&format!("\t\t\t\tv = v % {};\n", constant.literal()))
+			}
+		}
+
+		let src = format!(r#"{pragma}
+			__kernel void __main__(__global {ty}* buffer) {{
+				{body}
+				buffer[get_global_id(0)] = v;
+			}}
+		"#, pragma = T::PRAGMA, ty = T::NAME, body = body);
+
+		Program::builder()
+			.devices(devices)
+			.src(src)
+			.build(&context)
+			.map(|program| Self(program))
+	}
+
+	/// Creates a new program from a precompiled SPIR-V module, which must expose a `__main__` kernel with the `(__global float* buffer, float scalar)` signature
+	///
+	/// Untested: producing a valid SPIR-V module needs a SPIR-V assembler/compiler, which this crate doesn't otherwise depend on and this environment doesn't have.
+	pub fn from_spirv<D: Into<DeviceSpecifier>>(devices: D, binary: &[u32], context: &Context) -> Result<Self> {
+		let il: Vec<u8> = binary.iter().flat_map(|word| word.to_ne_bytes().to_vec()).collect();
+
+		Program::builder()
+			.devices(devices)
+			.il(il)
+			.build(&context)
+			.map(|program| Self(program))
+	}
 }
 
 impl MapKernel {
 	/// Creates a new kernel the runs given program using given queue to map an operation over given buffer with given value
-	fn from(program: &MapProgram, queue: Queue, buffer: &Buffer<f32>, val: &f32) -> Result<Self> {
+	pub fn from<T: Scalar>(program: &MapProgram, queue: Queue, buffer: &Buffer<T>, val: &T) -> Result<Self> {
 		let buffer_len = buffer.len();
 		Kernel::builder()
 		    .program(&program.0)
@@ -59,6 +181,142 @@ impl MapKernel {
 		    .map(|kernel| Self(kernel, buffer_len))
 	}
 
+	/// Creates a new kernel the runs given program using given queue to apply a chain of operations over given buffer in a single pass
+	pub fn from_chain<T: Scalar>(program: &MapProgram, queue: Queue, buffer: &Buffer<T>) -> Result<Self> {
+		let buffer_len = buffer.len();
+		Kernel::builder()
+		    .program(&program.0)
+		    .name("__main__")
+		    .queue(queue.clone())
+		    .global_work_size(buffer_len)
+		    .arg(buffer)
+		    .build()
+		    .map(|kernel| Self(kernel, buffer_len))
+	}
+
+	/// Creates a new kernel that runs given program using given queue to apply an operation element-wise between given `lhs` and `rhs` buffers, writing into given `out` buffer
+	pub fn from_binary<T: Scalar>(program: &MapProgram, queue: Queue, lhs: &Buffer<T>, rhs: &Buffer<T>, out: &Buffer<T>) -> Result<Self> {
+		let buffer_len = lhs.len();
+		if rhs.len() != buffer_len {
+			return Err("lhs and rhs buffers must share the same length".into());
+		}
+		if out.len() != buffer_len {
+			return Err("lhs and out buffers must share the same length".into());
+		}
+
+		Kernel::builder()
+		    .program(&program.0)
+		    .name("__main__")
+		    .queue(queue.clone())
+		    .global_work_size(buffer_len)
+		    .arg(lhs)
+		    .arg(rhs)
+		    .arg(out)
+		    .build()
+		    .map(|kernel| Self(kernel, buffer_len))
+	}
+
+	/// Executes the kernel
+	pub fn cmd_enq(&self, queue: &Queue) {
+		unsafe {
+		    self.0.cmd()
+		        .queue(&queue)
+		        .global_work_offset(self.0.default_global_work_offset())
+		        .global_work_size(self.1)
+		        .local_work_size(self.0.default_local_work_size())
+		        .enq().unwrap();
+		}
+	}
+
+	/// Enqueues the kernel without blocking, waiting on given dependency events first, and returns an event that completes when the kernel finishes
+	pub fn enq_async(&self, queue: &Queue, waitlist: &[Event]) -> Result<Event> {
+		let mut completion = Event::empty();
+		unsafe {
+		    self.0.cmd()
+		        .queue(&queue)
+		        .global_work_offset(self.0.default_global_work_offset())
+		        .global_work_size(self.1)
+		        .local_work_size(self.0.default_local_work_size())
+		        .ewait(waitlist)
+		        .enew(&mut completion)
+		        .enq()?;
+		}
+		Ok(completion)
+	}
+}
+
+/// A safe wrapper type for a Program that reduces a buffer to a single scalar
+pub struct ReduceProgram(Program, usize);
+/// A safe wrapper type for a Kernel that performs one level of a tree reduction
+pub struct ReduceKernel(Kernel, usize, usize);
+
+impl ReduceProgram {
+	/// Creates a new program that uses given device to reduce data to a single scalar using given operation and local work-group size with given context
+	///
+	/// `local_size` must be a power of two, since the tree reduction halves its stride each iteration.
+	pub fn from<D: Into<DeviceSpecifier>>(devices: D, op: Op, local_size: usize, context: &Context) -> Result<Self> {
+		if local_size == 0 || local_size & (local_size - 1) != 0 {
+			return Err(format!("local_size must be a power of two, got {}", local_size).into());
+		}
+
+		let (identity, combine) = match op {
+			Op::Add => ("0.0f", "a + b"),
+			Op::Min => ("INFINITY", "min(a, b)"),
+			Op::Max => ("-INFINITY", "max(a, b)"),
+			Op::Mul | Op::Div | Op::Mod | Op::None => return Err("only Add, Min, and Max are supported by ReduceProgram".into())
+		};
+
+		let src = format!(r#"
+			__kernel void __main__(__global float const* buffer, __local float* scratch, uint const len, __global float* partials) {{
+				uint const gid = get_global_id(0);
+				uint const lid = get_local_id(0);
+
+				scratch[lid] = gid < len ? buffer[gid] : {identity};
+
+				for (uint stride = get_local_size(0) / 2; stride > 0; stride >>= 1) {{
+					barrier(CLK_LOCAL_MEM_FENCE);
+					if (lid < stride) {{
+						float a = scratch[lid];
+						float b = scratch[lid + stride];
+						scratch[lid] = {combine};
+					}}
+				}}
+
+				if (lid == 0) {{
+					partials[get_group_id(0)] = scratch[0];
+				}}
+			}}
+		"#, identity = identity, combine = combine);
+
+		Program::builder()
+			.devices(devices)
+			.src(src)
+			.build(&context)
+			.map(|program| Self(program, local_size))
+	}
+}
+
+impl ReduceKernel {
+	/// Creates a new kernel that runs one level of given program's tree reduction over given buffer, writing group partials into given `partials` buffer
+	fn from(program: &ReduceProgram, queue: Queue, buffer: &Buffer<f32>, partials: &Buffer<f32>) -> Result<Self> {
+		let local_size = program.1;
+		let num_groups = (buffer.len() + local_size - 1) / local_size;
+		let global_size = num_groups * local_size;
+
+		Kernel::builder()
+		    .program(&program.0)
+		    .name("__main__")
+		    .queue(queue.clone())
+		    .global_work_size(global_size)
+		    .local_work_size(local_size)
+		    .arg(buffer)
+		    .arg_local::<f32>(local_size)
+		    .arg(&(buffer.len() as u32))
+		    .arg(partials)
+		    .build()
+		    .map(|kernel| Self(kernel, global_size, num_groups))
+	}
+
 	/// Executes the kernel
 	fn cmd_enq(&self, queue: &Queue) {
 		unsafe {
@@ -70,6 +328,104 @@ impl MapKernel {
 		        .enq().unwrap();
 		}
 	}
+
+	/// Reduces given buffer down to a single scalar, recursively reducing the partials buffer until one element remains
+	pub fn run(program: &ReduceProgram, queue: Queue, buffer: &Buffer<f32>) -> Result<f32> {
+		let mut len = buffer.len();
+		let local_size = program.1;
+		let num_groups = (len + local_size - 1) / local_size;
+
+		let partials = Buffer::<f32>::builder()
+			.queue(queue.clone())
+			.len(num_groups)
+			.fill_val(0f32)
+			.build()?;
+
+		let kernel = Self::from(program, queue.clone(), buffer, &partials)?;
+		kernel.cmd_enq(&queue);
+		len = num_groups;
+
+		let mut current = partials;
+		while len > 1 {
+			let num_groups = (len + local_size - 1) / local_size;
+
+			let partials = Buffer::<f32>::builder()
+				.queue(queue.clone())
+				.len(num_groups)
+				.fill_val(0f32)
+				.build()?;
+
+			let kernel = Self::from(program, queue.clone(), &current, &partials)?;
+			kernel.cmd_enq(&queue);
+
+			current = partials;
+			len = num_groups;
+		}
+
+		let mut result = vec![0.0f32; 1];
+		current.cmd()
+			.queue(&queue)
+			.offset(0)
+			.read(&mut result)
+			.enq()?;
+
+		Ok(result[0])
+	}
+}
+
+/// Splits a map across several devices, each handling its own context, program, queue and buffer
+pub struct MultiMap;
+
+impl MultiMap {
+	/// Applies given mapping operation with given scalar to given data, partitioning it into contiguous chunks and running one chunk per device, then gathers the results back into `data` in original order
+	pub fn run(devices: &[Device], op: Op, scalar: f32, data: &mut [f32]) -> Result<()> {
+		if devices.is_empty() {
+			return Err("MultiMap::run requires at least one device".into());
+		}
+
+		let len = data.len();
+		let num_devices = devices.len();
+		let chunk_size = (len + num_devices - 1) / num_devices;
+
+		let mut chunks = Vec::with_capacity(num_devices);
+
+		for (i, &device) in devices.iter().enumerate() {
+			let start = i * chunk_size;
+			if start >= len {
+				break;
+			}
+			let end = (start + chunk_size).min(len);
+
+			let context = Context::builder()
+				.platform(device.platform()?)
+				.devices(device)
+				.build()?;
+			let queue = Queue::new(&context, device, None)?;
+			let program = MapProgram::from::<f32, _>(device, op, &context)?;
+
+			let buffer = Buffer::<f32>::builder()
+				.queue(queue.clone())
+				.flags(flags::MEM_READ_WRITE)
+				.len(end - start)
+				.copy_host_slice(&data[start..end])
+				.build()?;
+
+			let kernel = MapKernel::from(&program, queue.clone(), &buffer, &scalar)?;
+			kernel.cmd_enq(&queue);
+
+			chunks.push((start, end, buffer, queue));
+		}
+
+		for (start, end, buffer, queue) in chunks {
+			buffer.cmd()
+				.queue(&queue)
+				.offset(0)
+				.read(&mut data[start..end])
+				.enq()?;
+		}
+
+		Ok(())
+	}
 }
 
 #[cfg(test)]
@@ -153,7 +509,7 @@ mod tests {
 	        .platform(platform)
 	        .devices(device.clone())
 	        .build().unwrap();
-	    let program = MapProgram::from(device, Op::Add, &context).unwrap();
+	    let program = MapProgram::from::<f32, _>(device, Op::Add, &context).unwrap();
 	    let queue = Queue::new(&context, device, None).unwrap();
 	    let dims = 1 << 20;
 	    // [NOTE]: At this point we could manually assemble a ProQue by calling:
@@ -185,4 +541,388 @@ mod tests {
 
 	    assert_eq!(vec, vec![10.0f32; dims]);
     }
+
+    #[test]
+    fn test_add_binary() {
+	    let platform = Platform::default();
+	    let device = Device::first(platform).unwrap();
+	    let context = Context::builder()
+	        .platform(platform)
+	        .devices(device.clone())
+	        .build().unwrap();
+	    let program = MapProgram::from_binary::<f32, _>(device, Op::Add, &context).unwrap();
+	    let queue = Queue::new(&context, device, None).unwrap();
+	    let dims = 1 << 20;
+
+	    let lhs = Buffer::<f32>::builder()
+	        .queue(queue.clone())
+	        .flags(flags::MEM_READ_WRITE)
+	        .len(dims)
+	        .fill_val(1.0f32)
+	        .build().unwrap();
+	    let rhs = Buffer::<f32>::builder()
+	        .queue(queue.clone())
+	        .flags(flags::MEM_READ_WRITE)
+	        .len(dims)
+	        .fill_val(10.0f32)
+	        .build().unwrap();
+	    let out = Buffer::<f32>::builder()
+	        .queue(queue.clone())
+	        .flags(flags::MEM_READ_WRITE)
+	        .len(dims)
+	        .fill_val(0.0f32)
+	        .build().unwrap();
+
+		let kernel = MapKernel::from_binary(&program, queue.clone(), &lhs, &rhs, &out).unwrap();
+
+		kernel.cmd_enq(&queue);
+
+	    let mut vec = vec![0.0f32; dims];
+	    out.cmd()
+	        .queue(&queue)
+	        .offset(0)
+	        .read(&mut vec)
+	        .enq().unwrap();
+
+	    assert_eq!(vec, vec![11.0f32; dims]);
+    }
+
+    #[test]
+    fn test_chain() {
+	    let platform = Platform::default();
+	    let device = Device::first(platform).unwrap();
+	    let context = Context::builder()
+	        .platform(platform)
+	        .devices(device.clone())
+	        .build().unwrap();
+	    let program = MapProgram::from_chain::<f32, _>(device, &[(Op::Add, 10.0), (Op::Mul, 2.0), (Op::Min, 1.0)], &context).unwrap();
+	    let queue = Queue::new(&context, device, None).unwrap();
+	    let dims = 1 << 20;
+
+	    let buffer = Buffer::<f32>::builder()
+	        .queue(queue.clone())
+	        .flags(flags::MEM_READ_WRITE)
+	        .len(dims)
+	        .fill_val(0f32)
+	        .build().unwrap();
+
+		let kernel = MapKernel::from_chain(&program, queue.clone(), &buffer).unwrap();
+
+		kernel.cmd_enq(&queue);
+
+	    let mut vec = vec![0.0f32; dims];
+	    buffer.cmd()
+	        .queue(&queue)
+	        .offset(0)
+	        .read(&mut vec)
+	        .enq().unwrap();
+
+	    assert_eq!(vec, vec![19.0f32; dims]);
+    }
+
+    #[test]
+    fn test_reduce_sum() {
+	    let platform = Platform::default();
+	    let device = Device::first(platform).unwrap();
+	    let context = Context::builder()
+	        .platform(platform)
+	        .devices(device.clone())
+	        .build().unwrap();
+	    let program = ReduceProgram::from(device, Op::Add, 256, &context).unwrap();
+	    let queue = Queue::new(&context, device, None).unwrap();
+	    let dims = 1 << 20;
+
+	    let buffer = Buffer::<f32>::builder()
+	        .queue(queue.clone())
+	        .flags(flags::MEM_READ_WRITE)
+	        .len(dims)
+	        .fill_val(1.0f32)
+	        .build().unwrap();
+
+	    let sum = ReduceKernel::run(&program, queue.clone(), &buffer).unwrap();
+
+	    assert_eq!(sum, dims as f32);
+    }
+
+    #[test]
+    fn test_reduce_min() {
+	    let platform = Platform::default();
+	    let device = Device::first(platform).unwrap();
+	    let context = Context::builder()
+	        .platform(platform)
+	        .devices(device.clone())
+	        .build().unwrap();
+	    let program = ReduceProgram::from(device, Op::Min, 256, &context).unwrap();
+	    let queue = Queue::new(&context, device, None).unwrap();
+	    let dims = 1 << 20;
+
+	    let data: Vec<f32> = (0..dims).map(|i| (dims - i) as f32).collect();
+	    let buffer = Buffer::<f32>::builder()
+	        .queue(queue.clone())
+	        .flags(flags::MEM_READ_WRITE)
+	        .len(dims)
+	        .copy_host_slice(&data)
+	        .build().unwrap();
+
+	    let min = ReduceKernel::run(&program, queue.clone(), &buffer).unwrap();
+
+	    assert_eq!(min, 1.0f32);
+    }
+
+    #[test]
+    fn test_reduce_max() {
+	    let platform = Platform::default();
+	    let device = Device::first(platform).unwrap();
+	    let context = Context::builder()
+	        .platform(platform)
+	        .devices(device.clone())
+	        .build().unwrap();
+	    let program = ReduceProgram::from(device, Op::Max, 256, &context).unwrap();
+	    let queue = Queue::new(&context, device, None).unwrap();
+	    let dims = 1 << 20;
+
+	    let data: Vec<f32> = (0..dims).map(|i| i as f32).collect();
+	    let buffer = Buffer::<f32>::builder()
+	        .queue(queue.clone())
+	        .flags(flags::MEM_READ_WRITE)
+	        .len(dims)
+	        .copy_host_slice(&data)
+	        .build().unwrap();
+
+	    let max = ReduceKernel::run(&program, queue.clone(), &buffer).unwrap();
+
+	    assert_eq!(max, (dims - 1) as f32);
+    }
+
+    #[test]
+    fn test_reduce_rejects_non_power_of_two_local_size() {
+	    let platform = Platform::default();
+	    let device = Device::first(platform).unwrap();
+	    let context = Context::builder()
+	        .platform(platform)
+	        .devices(device.clone())
+	        .build().unwrap();
+
+	    assert!(ReduceProgram::from(device, Op::Add, 100, &context).is_err());
+    }
+
+    #[test]
+    fn test_enq_async() {
+	    let platform = Platform::default();
+	    let device = Device::first(platform).unwrap();
+	    let context = Context::builder()
+	        .platform(platform)
+	        .devices(device.clone())
+	        .build().unwrap();
+	    let add_program = MapProgram::from::<f32, _>(device, Op::Add, &context).unwrap();
+	    let mul_program = MapProgram::from::<f32, _>(device, Op::Mul, &context).unwrap();
+	    let queue = Queue::new(&context, device, None).unwrap();
+	    let dims = 1 << 20;
+
+	    let buffer = Buffer::<f32>::builder()
+	        .queue(queue.clone())
+	        .flags(flags::MEM_READ_WRITE)
+	        .len(dims)
+	        .fill_val(1.0f32)
+	        .build().unwrap();
+
+		let add = MapKernel::from(&add_program, queue.clone(), &buffer, &10.0f32).unwrap();
+		let mul = MapKernel::from(&mul_program, queue.clone(), &buffer, &2.0f32).unwrap();
+
+		let e1 = add.enq_async(&queue, &[]).unwrap();
+		let e2 = mul.enq_async(&queue, &[e1]).unwrap();
+		e2.wait_for().unwrap();
+
+	    let mut vec = vec![0.0f32; dims];
+	    buffer.cmd()
+	        .queue(&queue)
+	        .offset(0)
+	        .read(&mut vec)
+	        .enq().unwrap();
+
+	    assert_eq!(vec, vec![22.0f32; dims]);
+    }
+
+    #[test]
+    fn test_add_i32() {
+	    let platform = Platform::default();
+	    let device = Device::first(platform).unwrap();
+	    let context = Context::builder()
+	        .platform(platform)
+	        .devices(device.clone())
+	        .build().unwrap();
+	    let program = MapProgram::from::<i32, _>(device, Op::Add, &context).unwrap();
+	    let queue = Queue::new(&context, device, None).unwrap();
+	    let dims = 1 << 20;
+
+	    let buffer = Buffer::<i32>::builder()
+	        .queue(queue.clone())
+	        .flags(flags::MEM_READ_WRITE)
+	        .len(dims)
+	        .fill_val(0i32)
+	        .build().unwrap();
+
+		let kernel = MapKernel::from(&program, queue.clone(), &buffer, &10i32).unwrap();
+
+		kernel.cmd_enq(&queue);
+
+	    let mut vec = vec![0i32; dims];
+	    buffer.cmd()
+	        .queue(&queue)
+	        .offset(0)
+	        .read(&mut vec)
+	        .enq().unwrap();
+
+	    assert_eq!(vec, vec![10i32; dims]);
+    }
+
+    #[test]
+    fn test_add_u32() {
+	    let platform = Platform::default();
+	    let device = Device::first(platform).unwrap();
+	    let context = Context::builder()
+	        .platform(platform)
+	        .devices(device.clone())
+	        .build().unwrap();
+	    let program = MapProgram::from::<u32, _>(device, Op::Add, &context).unwrap();
+	    let queue = Queue::new(&context, device, None).unwrap();
+	    let dims = 1 << 20;
+
+	    let buffer = Buffer::<u32>::builder()
+	        .queue(queue.clone())
+	        .flags(flags::MEM_READ_WRITE)
+	        .len(dims)
+	        .fill_val(0u32)
+	        .build().unwrap();
+
+		let kernel = MapKernel::from(&program, queue.clone(), &buffer, &10u32).unwrap();
+
+		kernel.cmd_enq(&queue);
+
+	    let mut vec = vec![0u32; dims];
+	    buffer.cmd()
+	        .queue(&queue)
+	        .offset(0)
+	        .read(&mut vec)
+	        .enq().unwrap();
+
+	    assert_eq!(vec, vec![10u32; dims]);
+    }
+
+    #[test]
+    fn test_add_f64() {
+	    let platform = Platform::default();
+	    let device = Device::first(platform).unwrap();
+	    let context = Context::builder()
+	        .platform(platform)
+	        .devices(device.clone())
+	        .build().unwrap();
+	    let program = MapProgram::from::<f64, _>(device, Op::Add, &context).unwrap();
+	    let queue = Queue::new(&context, device, None).unwrap();
+	    let dims = 1 << 20;
+
+	    let buffer = Buffer::<f64>::builder()
+	        .queue(queue.clone())
+	        .flags(flags::MEM_READ_WRITE)
+	        .len(dims)
+	        .fill_val(0.0f64)
+	        .build().unwrap();
+
+		let kernel = MapKernel::from(&program, queue.clone(), &buffer, &10.0f64).unwrap();
+
+		kernel.cmd_enq(&queue);
+
+	    let mut vec = vec![0.0f64; dims];
+	    buffer.cmd()
+	        .queue(&queue)
+	        .offset(0)
+	        .read(&mut vec)
+	        .enq().unwrap();
+
+	    assert_eq!(vec, vec![10.0f64; dims]);
+    }
+
+    #[test]
+    fn test_mod_f32() {
+	    let platform = Platform::default();
+	    let device = Device::first(platform).unwrap();
+	    let context = Context::builder()
+	        .platform(platform)
+	        .devices(device.clone())
+	        .build().unwrap();
+	    let program = MapProgram::from::<f32, _>(device, Op::Mod, &context).unwrap();
+	    let queue = Queue::new(&context, device, None).unwrap();
+	    let dims = 1 << 20;
+
+	    let buffer = Buffer::<f32>::builder()
+	        .queue(queue.clone())
+	        .flags(flags::MEM_READ_WRITE)
+	        .len(dims)
+	        .fill_val(7.5f32)
+	        .build().unwrap();
+
+		let kernel = MapKernel::from(&program, queue.clone(), &buffer, &3.0f32).unwrap();
+
+		kernel.cmd_enq(&queue);
+
+	    let mut vec = vec![0.0f32; dims];
+	    buffer.cmd()
+	        .queue(&queue)
+	        .offset(0)
+	        .read(&mut vec)
+	        .enq().unwrap();
+
+	    assert_eq!(vec, vec![1.5f32; dims]);
+    }
+
+    #[test]
+    fn test_mod_u32() {
+	    let platform = Platform::default();
+	    let device = Device::first(platform).unwrap();
+	    let context = Context::builder()
+	        .platform(platform)
+	        .devices(device.clone())
+	        .build().unwrap();
+	    let program = MapProgram::from::<u32, _>(device, Op::Mod, &context).unwrap();
+	    let queue = Queue::new(&context, device, None).unwrap();
+	    let dims = 1 << 20;
+
+	    let buffer = Buffer::<u32>::builder()
+	        .queue(queue.clone())
+	        .flags(flags::MEM_READ_WRITE)
+	        .len(dims)
+	        .fill_val(7u32)
+	        .build().unwrap();
+
+		let kernel = MapKernel::from(&program, queue.clone(), &buffer, &3u32).unwrap();
+
+		kernel.cmd_enq(&queue);
+
+	    let mut vec = vec![0u32; dims];
+	    buffer.cmd()
+	        .queue(&queue)
+	        .offset(0)
+	        .read(&mut vec)
+	        .enq().unwrap();
+
+	    assert_eq!(vec, vec![1u32; dims]);
+    }
+
+    #[test]
+    fn test_multi_map() {
+	    let platform = Platform::default();
+	    let devices = vec![Device::first(platform).unwrap()];
+	    let dims = 1 << 20;
+
+	    let mut data = vec![0.0f32; dims];
+	    MultiMap::run(&devices, Op::Add, 10.0f32, &mut data).unwrap();
+
+	    assert_eq!(data, vec![10.0f32; dims]);
+    }
+
+    #[test]
+    fn test_multi_map_rejects_no_devices() {
+	    let mut data = vec![0.0f32; 16];
+	    assert!(MultiMap::run(&[], Op::Add, 10.0f32, &mut data).is_err());
+    }
 }